@@ -1,10 +1,60 @@
-use ndarray::{ArrayBase, Data, Ix1, RawData};
+use std::cmp::Ordering;
+
+use ndarray::{s, ArrayBase, CowArray, Data, Ix1, RawData};
+use num_traits::Float;
 
 ///! This module contains the vector extensions trait
 
 pub trait VectorExtensions {
+    /// the element type of the vector
+    type Elem;
+
     /// get the monotonic property of the vector
     fn monotonic_prop(&self) -> Monotonic;
+
+    /// Like [`VectorExtensions::monotonic_prop`], but pinpoints the first
+    /// window at which the vector fails to be monotonic instead of just
+    /// returning [`Monotonic::NotMonotonic`].
+    ///
+    /// A vector with fewer than two elements, or whose elements are all
+    /// equal, has no trend that could be broken; for it this returns
+    /// `Ok(Monotonic::NotMonotonic)`, just like `monotonic_prop`.
+    ///
+    /// # Errors
+    /// Returns a [`MonotonicViolation`] describing the index and values of
+    /// the first element that broke the trend established by the elements
+    /// before it.
+    fn monotonic_check(&self) -> Result<Monotonic, MonotonicViolation<Self::Elem>>
+    where
+        Self::Elem: Clone;
+
+    /// Find the interval of this vector containing `value`.
+    ///
+    /// The vector must be monotonic (see [`VectorExtensions::monotonic_prop`]);
+    /// both rising and falling vectors are supported. This performs a binary
+    /// search, so lookups are `O(log n)` instead of the linear scan an
+    /// interpolator would otherwise have to implement itself.
+    ///
+    /// # Errors
+    /// Returns [`IndexSearchError::NotMonotonic`] if the vector is not monotonic,
+    /// or [`IndexSearchError::NotComparable`] if `value` can not be compared to
+    /// an element of the vector (e.g. `value` is a NaN).
+    fn find_index(&self, value: &Self::Elem) -> Result<IndexSearch, IndexSearchError>;
+
+    /// Get a view of this vector in ascending order.
+    ///
+    /// For a strictly [`Monotonic::Rising`] vector this is just the original
+    /// view. For a strictly [`Monotonic::Falling`] vector this is a reversed
+    /// view of the same data, so no copy is made. The returned `bool` is
+    /// `true` if the vector was reversed.
+    ///
+    /// This lets interpolators accept data sorted in either direction (e.g.
+    /// depth or pressure axes, which commonly decrease) without requiring
+    /// callers to flip their x and y arrays themselves.
+    ///
+    /// # Errors
+    /// Returns [`IndexSearchError::NotMonotonic`] if the vector is not monotonic.
+    fn as_ascending(&self) -> Result<(CowArray<'_, Self::Elem, Ix1>, bool), IndexSearchError>;
 }
 
 /// Describes the monotonic property of a vector
@@ -16,81 +66,281 @@ pub enum Monotonic {
 }
 use Monotonic::*;
 
-impl<S, T> VectorExtensions for ArrayBase<S, Ix1>
+/// The direction of the trend expected at the point where a
+/// [`VectorExtensions::monotonic_check`] failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Rising,
+    Falling,
+}
+
+/// Returned by [`VectorExtensions::monotonic_check`] when a vector is not
+/// monotonic, pinpointing the first element that broke the trend.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MonotonicViolation<T> {
+    /// index of the element that broke the established trend
+    pub index: usize,
+    /// the element preceding the violation, at `index - 1`
+    pub prev: T,
+    /// the offending element, at `index`
+    pub curr: T,
+    /// the direction the vector was trending in before the violation
+    pub expected_direction: Direction,
+}
+
+/// The result of [`VectorExtensions::find_index`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndexSearch {
+    /// the value is equal to the element at this index
+    Exact(usize),
+    /// the value lies strictly between the elements at `index` and `index + 1`
+    Between(usize),
+    /// the value is below the lowest element of the vector
+    BelowRange,
+    /// the value is above the highest element of the vector
+    AboveRange,
+}
+
+/// An error returned by [`VectorExtensions::find_index`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndexSearchError {
+    /// the vector is not monotonic, so no interval can be located
+    NotMonotonic,
+    /// the searched-for value could not be compared to an element of the
+    /// vector (e.g. it is a NaN)
+    NotComparable,
+}
+
+impl std::fmt::Display for IndexSearchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IndexSearchError::NotMonotonic => {
+                write!(f, "can not search for an index in a non monotonic vector")
+            }
+            IndexSearchError::NotComparable => {
+                write!(
+                    f,
+                    "the searched-for value can not be compared to the vector's elements"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for IndexSearchError {}
+
+/// Shared trend-tracking fold behind [`VectorExtensions::monotonic_prop`],
+/// [`VectorExtensions::monotonic_check`], and
+/// [`VectorExtensionsTol::monotonic_prop_tol`].
+///
+/// Walks adjacent pairs of `arr`, treating two elements as equal when
+/// `nearly_eq` says so, and otherwise tracking whether the vector is rising
+/// or falling. The first time the established trend is broken, `on_violation`
+/// is called with the index of the offending element (the second element of
+/// the violating window), the previous and current elements, and the
+/// direction that was expected; its return value becomes this fold's `Err`.
+fn monotonic_fold<S, T, E>(
+    arr: &ArrayBase<S, Ix1>,
+    nearly_eq: impl Fn(&T, &T) -> bool,
+    mut on_violation: impl FnMut(usize, &T, &T, Direction) -> E,
+) -> Result<Monotonic, E>
 where
     S: RawData<Elem = T> + Data,
     T: PartialOrd,
 {
-    fn monotonic_prop(&self) -> Monotonic {
-        if self.len() <= 1 {
-            return NotMonotonic;
-        };
+    if arr.len() <= 1 {
+        return Ok(NotMonotonic);
+    };
 
-        #[derive(Debug)]
-        enum State {
-            Init,
-            NotStrict,
-            Known(Monotonic),
-        }
-        use State::*;
-
-        let state = self
-            .windows(2)
-            .into_iter()
-            .try_fold(Init, |state, items| {
-                let a = items.get(0).unwrap_or_else(|| unreachable!());
-                let b = items.get(1).unwrap_or_else(|| unreachable!());
-                match state {
-                    Init => {
-                        if a < b {
-                            return Ok(Known(Rising { strict: true }));
-                        } else if a == b {
-                            return Ok(NotStrict);
-                        }
-                        Ok(Known(Falling { strict: true }))
+    #[derive(Debug)]
+    enum State {
+        Init,
+        NotStrict,
+        Known(Monotonic),
+    }
+    use State::*;
+
+    let state = arr
+        .windows(2)
+        .into_iter()
+        .enumerate()
+        .try_fold(Init, |state, (i, items)| {
+            let a = items.get(0).unwrap_or_else(|| unreachable!());
+            let b = items.get(1).unwrap_or_else(|| unreachable!());
+            match state {
+                Init => {
+                    if nearly_eq(a, b) {
+                        return Ok(NotStrict);
+                    } else if a < b {
+                        return Ok(Known(Rising { strict: true }));
                     }
-                    NotStrict => {
-                        if a < b {
-                            return Ok(Known(Rising { strict: false }));
-                        } else if a == b {
-                            return Ok(NotStrict);
-                        }
-                        Ok(Known(Falling { strict: false }))
+                    Ok(Known(Falling { strict: true }))
+                }
+                NotStrict => {
+                    if nearly_eq(a, b) {
+                        return Ok(NotStrict);
+                    } else if a < b {
+                        return Ok(Known(Rising { strict: false }));
                     }
-                    Known(Rising { strict }) => {
-                        if a == b {
-                            return Ok(Known(Rising { strict: false }));
-                        } else if a < b {
-                            return Ok(Known(Rising { strict }));
-                        }
-                        Err(NotMonotonic)
+                    Ok(Known(Falling { strict: false }))
+                }
+                Known(Rising { strict }) => {
+                    if nearly_eq(a, b) {
+                        return Ok(Known(Rising { strict: false }));
+                    } else if a < b {
+                        return Ok(Known(Rising { strict }));
                     }
-                    Known(Falling { strict }) => {
-                        if a == b {
-                            return Ok(Known(Falling { strict: false }));
-                        } else if a > b {
-                            return Ok(Known(Falling { strict }));
-                        }
-                        Err(NotMonotonic)
+                    Err(on_violation(i + 1, a, b, Direction::Rising))
+                }
+                Known(Falling { strict }) => {
+                    if nearly_eq(a, b) {
+                        return Ok(Known(Falling { strict: false }));
+                    } else if a > b {
+                        return Ok(Known(Falling { strict }));
                     }
-                    Known(NotMonotonic) => unreachable!(),
+                    Err(on_violation(i + 1, a, b, Direction::Falling))
                 }
-            })
-            .unwrap_or(Known(NotMonotonic));
+                Known(NotMonotonic) => unreachable!(),
+            }
+        })?;
+
+    Ok(match state {
+        Known(state) => state,
+        _ => NotMonotonic,
+    })
+}
 
-        if let Known(state) = state {
-            state
+impl<S, T> VectorExtensions for ArrayBase<S, Ix1>
+where
+    S: RawData<Elem = T> + Data,
+    T: PartialOrd,
+{
+    type Elem = T;
+
+    fn monotonic_prop(&self) -> Monotonic {
+        monotonic_fold(self, |a, b| a == b, |_, _, _, direction| direction).unwrap_or(NotMonotonic)
+    }
+
+    fn monotonic_check(&self) -> Result<Monotonic, MonotonicViolation<T>>
+    where
+        T: Clone,
+    {
+        monotonic_fold(
+            self,
+            |a, b| a == b,
+            |index, prev, curr, expected_direction| MonotonicViolation {
+                index,
+                prev: prev.clone(),
+                curr: curr.clone(),
+                expected_direction,
+            },
+        )
+    }
+
+    fn find_index(&self, value: &T) -> Result<IndexSearch, IndexSearchError> {
+        let ascending = match self.monotonic_prop() {
+            Rising { .. } => true,
+            Falling { .. } => false,
+            NotMonotonic => return Err(IndexSearchError::NotMonotonic),
+        };
+
+        let len = self.len();
+        let (lowest, highest) = if ascending {
+            (&self[0], &self[len - 1])
         } else {
-            NotMonotonic
+            (&self[len - 1], &self[0])
+        };
+
+        if value
+            .partial_cmp(lowest)
+            .ok_or(IndexSearchError::NotComparable)?
+            == Ordering::Less
+        {
+            return Ok(IndexSearch::BelowRange);
+        }
+        if value
+            .partial_cmp(highest)
+            .ok_or(IndexSearchError::NotComparable)?
+            == Ordering::Greater
+        {
+            return Ok(IndexSearch::AboveRange);
+        }
+
+        // compare `value` against the element at `mid`, always in the
+        // vector's own ascending/descending sense
+        let compare = |probe: &T| -> Result<Ordering, IndexSearchError> {
+            let ord = value
+                .partial_cmp(probe)
+                .ok_or(IndexSearchError::NotComparable)?;
+            Ok(if ascending { ord } else { ord.reverse() })
+        };
+
+        let mut lo = 0usize;
+        let mut hi = len;
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            match compare(&self[mid])? {
+                Ordering::Equal => return Ok(IndexSearch::Exact(mid)),
+                Ordering::Less => hi = mid,
+                Ordering::Greater => lo = mid + 1,
+            }
+        }
+        Ok(IndexSearch::Between(lo - 1))
+    }
+
+    fn as_ascending(&self) -> Result<(CowArray<'_, T, Ix1>, bool), IndexSearchError> {
+        match self.monotonic_prop() {
+            Rising { .. } => Ok((CowArray::from(self.view()), false)),
+            Falling { .. } => Ok((CowArray::from(self.slice(s![..;-1])), true)),
+            NotMonotonic => Err(IndexSearchError::NotMonotonic),
         }
     }
 }
 
+/// Extension trait for a tolerance-based monotonicity check on floating
+/// point vectors.
+///
+/// `T: PartialOrd`, used by [`VectorExtensions::monotonic_prop`], is exact
+/// and treats `x[i]` and `x[i + 1]` as equal only when they compare equal.
+/// Real world `f64`/`f32` coordinate grids often carry tiny rounding noise
+/// that would otherwise make a conceptually-increasing grid register as
+/// [`Monotonic::NotMonotonic`], so this trait is restricted to `T: Float`
+/// and compares adjacent elements with an absolute/relative tolerance
+/// instead.
+pub trait VectorExtensionsTol {
+    /// the element type of the vector
+    type Elem;
+
+    /// get the monotonic property of the vector, treating two adjacent
+    /// elements `a` and `b` as equal when `|a - b| <= atol + rtol * max(|a|, |b|)`
+    fn monotonic_prop_tol(&self, atol: Self::Elem, rtol: Self::Elem) -> Monotonic;
+}
+
+impl<S, T> VectorExtensionsTol for ArrayBase<S, Ix1>
+where
+    S: RawData<Elem = T> + Data,
+    T: Float,
+{
+    type Elem = T;
+
+    fn monotonic_prop_tol(&self, atol: T, rtol: T) -> Monotonic {
+        let nearly_eq = |a: &T, b: &T| {
+            let tol = atol + rtol * a.abs().max(b.abs());
+            (*a - *b).abs() <= tol
+        };
+
+        monotonic_fold(self, nearly_eq, |_, _, _, direction| direction).unwrap_or(NotMonotonic)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use ndarray::{array, s, Array1};
 
-    use super::{Monotonic, VectorExtensions};
+    use super::{
+        Direction, IndexSearch, IndexSearchError, Monotonic, MonotonicViolation, VectorExtensions,
+        VectorExtensionsTol,
+    };
 
     macro_rules! test_monotonic {
         ($d:ident, $expected:pat) => {
@@ -191,4 +441,171 @@ mod test {
         let data: Array1<i32> = array![1];
         test_monotonic!(data, Monotonic::NotMonotonic);
     }
+
+    // test find_index
+    #[test]
+    fn test_find_index_exact_rising() {
+        let data: Array1<i32> = array![1, 2, 3, 4, 5];
+        assert_eq!(data.find_index(&3).unwrap(), IndexSearch::Exact(2));
+    }
+
+    #[test]
+    fn test_find_index_between_rising() {
+        let data: Array1<f64> = array![1.0, 2.0, 3.0, 4.0, 5.0];
+        assert_eq!(data.find_index(&2.5).unwrap(), IndexSearch::Between(1));
+    }
+
+    #[test]
+    fn test_find_index_below_and_above_range_rising() {
+        let data: Array1<i32> = array![1, 2, 3, 4, 5];
+        assert_eq!(data.find_index(&0).unwrap(), IndexSearch::BelowRange);
+        assert_eq!(data.find_index(&6).unwrap(), IndexSearch::AboveRange);
+    }
+
+    #[test]
+    fn test_find_index_exact_falling() {
+        let data: Array1<i32> = array![5, 4, 3, 2, 1];
+        assert_eq!(data.find_index(&3).unwrap(), IndexSearch::Exact(2));
+    }
+
+    #[test]
+    fn test_find_index_between_falling() {
+        let data: Array1<f64> = array![5.0, 4.0, 3.0, 2.0, 1.0];
+        assert_eq!(data.find_index(&3.5).unwrap(), IndexSearch::Between(1));
+    }
+
+    #[test]
+    fn test_find_index_below_and_above_range_falling() {
+        let data: Array1<i32> = array![5, 4, 3, 2, 1];
+        assert_eq!(data.find_index(&0).unwrap(), IndexSearch::BelowRange);
+        assert_eq!(data.find_index(&6).unwrap(), IndexSearch::AboveRange);
+    }
+
+    #[test]
+    fn test_find_index_not_monotonic() {
+        let data: Array1<i32> = array![1, 2, 3, 2, 4, 5];
+        assert_eq!(
+            data.find_index(&3).unwrap_err(),
+            IndexSearchError::NotMonotonic
+        );
+    }
+
+    #[test]
+    fn test_find_index_not_comparable() {
+        let data: Array1<f64> = array![1.0, 2.0, 3.0, 4.0, 5.0];
+        assert_eq!(
+            data.find_index(&f64::NAN).unwrap_err(),
+            IndexSearchError::NotComparable
+        );
+    }
+
+    // test as_ascending
+    #[test]
+    fn test_as_ascending_rising() {
+        let data: Array1<i32> = array![1, 2, 3, 4, 5];
+        let (ascending, reversed) = data.as_ascending().unwrap();
+        assert!(!reversed);
+        assert_eq!(ascending, data);
+    }
+
+    #[test]
+    fn test_as_ascending_falling() {
+        let data: Array1<i32> = array![5, 4, 3, 2, 1];
+        let (ascending, reversed) = data.as_ascending().unwrap();
+        assert!(reversed);
+        assert_eq!(ascending, array![1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn test_as_ascending_not_monotonic() {
+        let data: Array1<i32> = array![1, 3, 2, 5];
+        assert_eq!(
+            data.as_ascending().unwrap_err(),
+            IndexSearchError::NotMonotonic
+        );
+    }
+
+    // test monotonic_prop_tol
+    #[test]
+    fn test_monotonic_prop_tol_rising_within_tolerance() {
+        let data: Array1<f64> = array![1.0, 2.0 + 1e-10, 3.0 - 1e-10, 4.0];
+        match data.monotonic_prop_tol(1e-6, 1e-6) {
+            Monotonic::Rising { strict: true } => (),
+            value => panic!("{}", format!("got {value:?}")),
+        }
+    }
+
+    #[test]
+    fn test_monotonic_prop_tol_falling_within_tolerance() {
+        let data: Array1<f64> = array![4.0, 3.0 + 1e-10, 2.0 - 1e-10, 1.0];
+        match data.monotonic_prop_tol(1e-6, 1e-6) {
+            Monotonic::Falling { strict: true } => (),
+            value => panic!("{}", format!("got {value:?}")),
+        }
+    }
+
+    #[test]
+    fn test_monotonic_prop_tol_exceeds_tolerance() {
+        let data: Array1<f64> = array![1.0, 2.0, 1.999, 4.0];
+        match data.monotonic_prop_tol(1e-6, 1e-6) {
+            Monotonic::NotMonotonic => (),
+            value => panic!("{}", format!("got {value:?}")),
+        }
+    }
+
+    #[test]
+    fn test_monotonic_prop_tol_treats_noise_as_flat() {
+        let data: Array1<f64> = array![1.0, 1.0 + 1e-12, 1.0 - 1e-12];
+        match data.monotonic_prop_tol(1e-6, 1e-6) {
+            Monotonic::NotMonotonic => (),
+            value => panic!("{}", format!("got {value:?}")),
+        }
+    }
+
+    // test monotonic_check
+    #[test]
+    fn test_monotonic_check_rising() {
+        let data: Array1<i32> = array![1, 2, 3, 4, 5];
+        match data.monotonic_check().unwrap() {
+            Monotonic::Rising { strict: true } => (),
+            value => panic!("{}", format!("got {value:?}")),
+        }
+    }
+
+    #[test]
+    fn test_monotonic_check_falling() {
+        let data: Array1<i32> = array![5, 4, 3, 2, 1];
+        match data.monotonic_check().unwrap() {
+            Monotonic::Falling { strict: true } => (),
+            value => panic!("{}", format!("got {value:?}")),
+        }
+    }
+
+    #[test]
+    fn test_monotonic_check_reports_first_violation() {
+        let data: Array1<i32> = array![1, 2, 3, 2, 4, 5];
+        assert_eq!(
+            data.monotonic_check().unwrap_err(),
+            MonotonicViolation {
+                index: 3,
+                prev: 3,
+                curr: 2,
+                expected_direction: Direction::Rising,
+            }
+        );
+    }
+
+    #[test]
+    fn test_monotonic_check_reports_plateau_turned_reversal() {
+        let data: Array1<i32> = array![1, 2, 2, 1];
+        assert_eq!(
+            data.monotonic_check().unwrap_err(),
+            MonotonicViolation {
+                index: 3,
+                prev: 2,
+                curr: 1,
+                expected_direction: Direction::Rising,
+            }
+        );
+    }
 }